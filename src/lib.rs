@@ -1,11 +1,15 @@
+pub mod money;
+pub mod predicate;
 pub mod template;
 
-use std::{collections::HashMap, str, vec::Vec};
+use std::{borrow::Cow, collections::HashMap, str, str::FromStr, vec::Vec};
 
 use chrono::NaiveDate;
 use regex::{Regex, RegexSet, RegexSetBuilder};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::predicate::Predicate;
 use crate::template::interpolate;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,6 +28,15 @@ impl DecimalSeparator {
     }
 }
 
+/// Configures how an `Inflow`/`Outflow` column's cells are parsed into a
+/// `Decimal`, see `money::parse`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AmountFormat {
+    pub separator: DecimalSeparator,
+    #[serde(default)]
+    pub currency_symbol: Option<String>,
+}
+
 /// See: https://docs.youneedabudget.com/article/921-formatting-csv-file
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase", tag = "type", content = "args")]
@@ -33,8 +46,8 @@ pub enum Field {
     Payee,
     Category,
     Memo,
-    Inflow(DecimalSeparator),
-    Outflow(DecimalSeparator),
+    Inflow(AmountFormat),
+    Outflow(AmountFormat),
     Extra(String),
 }
 
@@ -44,7 +57,7 @@ pub struct Record {
     pub payee: String,
     pub category: String,
     pub memo: String,
-    pub amount: String,
+    pub amount: Decimal,
     pub extra: HashMap<String, String>,
     pub transformed: bool,
 }
@@ -56,7 +69,7 @@ impl Record {
             payee: String::new(),
             category: String::new(),
             memo: String::new(),
-            amount: String::new(),
+            amount: Decimal::ZERO,
             extra: HashMap::new(),
             transformed: false,
         }
@@ -68,42 +81,56 @@ impl Record {
     ) -> Self {
         let mut r = Self::new();
         for (i, col) in mapping.into_iter().enumerate() {
-            let v = input
-                .get(i)
-                .expect("input record has less columns than expected")
-                .to_owned();
-            match col {
-                Field::Ignore => continue,
-                Field::Date(format) => {
-                    if format.is_empty() {
-                        r.date = v;
-                    } else {
-                        let date = NaiveDate::parse_from_str(&v, format)
-                            .expect("date or date format is malformed");
-                        r.date = date.format("%Y-%m-%d").to_string();
-                    }
-                }
-                Field::Payee => r.payee = v,
-                Field::Category => r.category = v,
-                Field::Memo => r.memo = v,
-                Field::Inflow(sep) => r.amount = sep.simplify(&v),
-                Field::Outflow(sep) => r.amount = format!("-{}", sep.simplify(&v)),
-                Field::Extra(key) => {
-                    r.extra.insert(key.to_owned(), v);
+            // With `flexible` csv parsing, a row may have fewer columns
+            // than configured; treat any column past the row's end as
+            // empty rather than panicking.
+            let v = input.get(i).unwrap_or("").to_owned();
+            r.apply_field(col, v);
+        }
+        r
+    }
+
+    /// Routes a single cell's raw text `v` into the field of this record
+    /// that `field` describes. Shared by every backend (csv, pdf, ...) that
+    /// turns raw cells into a `Record`.
+    pub fn apply_field(&mut self, field: &Field, v: String) {
+        match field {
+            Field::Ignore => {}
+            Field::Date(format) => {
+                if format.is_empty() {
+                    self.date = v;
+                } else {
+                    let date = NaiveDate::parse_from_str(&v, format)
+                        .expect("date or date format is malformed");
+                    self.date = date.format("%Y-%m-%d").to_string();
                 }
             }
+            Field::Payee => self.payee = v,
+            Field::Category => self.category = v,
+            Field::Memo => self.memo = v,
+            // Added rather than assigned, so banks that split a
+            // transaction's debit and credit across two columns don't
+            // clobber one another.
+            Field::Inflow(fmt) => {
+                self.amount += money::parse(&v, &fmt.separator, fmt.currency_symbol.as_deref())
+            }
+            Field::Outflow(fmt) => {
+                self.amount -= money::parse(&v, &fmt.separator, fmt.currency_symbol.as_deref())
+            }
+            Field::Extra(key) => {
+                self.extra.insert(key.to_owned(), v);
+            }
         }
-        r
     }
 
-    pub fn get(&self, key: &str) -> Option<&str> {
+    pub fn get(&self, key: &str) -> Option<Cow<'_, str>> {
         match key {
-            "date" => Some(&self.date),
-            "payee" => Some(&self.payee),
-            "category" => Some(&self.category),
-            "memo" => Some(&self.memo),
-            "amount" => Some(&self.amount),
-            key => self.extra.get(key).map(|x| x.as_str()),
+            "date" => Some(Cow::Borrowed(&self.date)),
+            "payee" => Some(Cow::Borrowed(&self.payee)),
+            "category" => Some(Cow::Borrowed(&self.category)),
+            "memo" => Some(Cow::Borrowed(&self.memo)),
+            "amount" => Some(Cow::Owned(format!("{:.2}", self.amount))),
+            key => self.extra.get(key).map(|x| Cow::Borrowed(x.as_str())),
         }
     }
 
@@ -118,7 +145,13 @@ impl Record {
             "payee" => Some(std::mem::replace(&mut self.payee, value)),
             "category" => Some(std::mem::replace(&mut self.category, value)),
             "memo" => Some(std::mem::replace(&mut self.memo, value)),
-            "amount" => Some(std::mem::replace(&mut self.amount, value)),
+            "amount" => match Decimal::from_str(&value) {
+                Ok(parsed) => Some(format!("{:.2}", std::mem::replace(&mut self.amount, parsed))),
+                Err(e) => {
+                    eprintln!("warning: skipping replace of `amount`: {}", e);
+                    None
+                }
+            },
             key => self.extra.insert(key.to_string(), value),
         }
     }
@@ -142,11 +175,11 @@ impl YnabRecord for Record {
 
     fn to_record(&self) -> csv::StringRecord {
         csv::StringRecord::from(vec![
-            &self.date,
-            &self.payee,
-            &self.category,
-            &self.memo,
-            &self.amount,
+            self.date.clone(),
+            self.payee.clone(),
+            self.category.clone(),
+            self.memo.clone(),
+            format!("{:.2}", self.amount),
         ])
     }
 }
@@ -161,6 +194,11 @@ pub struct MatcherBuilder {
     pub label: Option<String>,
     #[serde(rename = "match")]
     pub search: HashMap<String, String>,
+    /// A predicate expression ANDed together with `search`, for conditions
+    /// a flat per-field regex can't express (numeric/date comparisons,
+    /// ranges, or/not).
+    #[serde(default)]
+    pub when: Option<Predicate>,
     pub replace: HashMap<String, String>,
 }
 
@@ -172,6 +210,7 @@ impl MatcherBuilder {
                     .into_iter()
                     .map(|(k, v)| (k, Regex::new(&v).unwrap())),
             ),
+            when: self.when,
             replace: self.replace,
         }
     }
@@ -186,6 +225,7 @@ impl From<MatcherBuilder> for Matcher {
 #[derive(Debug)]
 pub struct Matcher {
     search: HashMap<String, Regex>,
+    when: Option<Predicate>,
     replace: HashMap<String, String>,
 }
 
@@ -193,6 +233,7 @@ impl Default for Matcher {
     fn default() -> Self {
         Self {
             search: HashMap::new(),
+            when: None,
             replace: HashMap::new(),
         }
     }
@@ -201,9 +242,12 @@ impl Default for Matcher {
 impl Transformer for Matcher {
     fn is_match(&self, record: &Record) -> bool {
         self.search.iter().all(|(k, v)| match record.get(k) {
-            Some(field) => v.is_match(field),
+            Some(field) => v.is_match(&field),
             None => false,
-        })
+        }) && self
+            .when
+            .as_ref()
+            .is_none_or(|p| p.eval(record, &mut HashMap::new()))
     }
 
     fn transform(&self, record: &mut Record) -> bool {
@@ -230,17 +274,26 @@ impl Transformer for Matcher {
             }
         }
 
+        if let Some(p) = &self.when {
+            if !p.eval(record, &mut captures) {
+                return false;
+            }
+        }
+
         for (k, v) in &self.replace {
-            record.replace(
-                k,
-                interpolate(v, |key: &str| {
-                    captures
-                        .get(key)
-                        .map(|x| x.to_string())
-                        .or_else(|| record.get(key).map(|x| x.to_string()))
-                        .unwrap_or_default()
-                }),
-            );
+            let value = interpolate(v, |key: &str| {
+                captures
+                    .get(key)
+                    .map(|x| x.to_string())
+                    .or_else(|| record.get(key).map(|x| x.to_string()))
+                    .unwrap_or_default()
+            });
+            match value {
+                Ok(value) => {
+                    record.replace(k, value);
+                }
+                Err(e) => eprintln!("warning: skipping replace of `{}`: {}", k, e),
+            }
         }
 
         record.transformed = true;
@@ -1,3 +1,5 @@
+mod pdf;
+
 use std::{collections::HashMap, path::Path, str, vec::Vec};
 
 use clap::{App, Arg};
@@ -9,6 +11,7 @@ use serde_regex;
 use serde_yaml::{self};
 use thiserror::Error;
 
+use pdf::PdfParser;
 use ynap::{Field, Matcher, MatcherBuilder, Payees, Record, Transformer, YnabRecord};
 
 #[derive(Error, Debug)]
@@ -16,8 +19,38 @@ pub enum AppError {
     #[error("error parsing CSV file: {}", .0)]
     Csv(#[from] csv::Error),
 
+    #[error("error parsing PDF file: {}", .0)]
+    Pdf(#[from] lopdf::Error),
+
     #[error("input/output error: {}", .0)]
     Io(#[from] std::io::Error),
+
+    #[error("no bank parser in {} matches input file {}", .0, .1)]
+    NoMatchingParser(String, String),
+
+    #[error("invalid record on line {}: {}", .0, .1)]
+    InvalidRecord(u64, csv::Error),
+}
+
+/// Mirrors `csv::Trim`, see its documentation for the meaning of each variant.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trim {
+    None,
+    Headers,
+    Fields,
+    All,
+}
+
+impl From<Trim> for csv::Trim {
+    fn from(t: Trim) -> Self {
+        match t {
+            Trim::None => csv::Trim::None,
+            Trim::Headers => csv::Trim::Headers,
+            Trim::Fields => csv::Trim::Fields,
+            Trim::All => csv::Trim::All,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -29,6 +62,16 @@ pub struct Parser {
     pub ignore_patterns: Vec<Regex>,
     pub ignore_header_rows: usize,
     pub delimiter: String,
+    #[serde(default)]
+    pub trim: Option<Trim>,
+    #[serde(default)]
+    pub quote: Option<char>,
+    #[serde(default)]
+    pub escape: Option<char>,
+    #[serde(default)]
+    pub flexible: bool,
+    #[serde(default)]
+    pub comment: Option<char>,
     pub columns: Vec<Field>,
 }
 
@@ -94,18 +137,98 @@ impl Parser {
             .collect();
 
         // Convert the CSV records into ynap::Records.
-        let records = csv::ReaderBuilder::new()
+        let mut builder = csv::ReaderBuilder::new();
+        builder
             .delimiter(self.delimiter.as_bytes()[0])
             .has_headers(false)
+            .flexible(self.flexible);
+        if let Some(trim) = self.trim {
+            builder.trim(trim.into());
+        }
+        if let Some(quote) = self.quote {
+            builder.quote(quote as u8);
+        }
+        if let Some(escape) = self.escape {
+            builder.escape(Some(escape as u8));
+        }
+        if let Some(comment) = self.comment {
+            builder.comment(Some(comment as u8));
+        }
+
+        let records = builder
             .from_reader(input.as_bytes())
             .records()
-            .map(|x| Record::from(&x.expect("invalid line in file"), self.columns.iter()))
-            .collect();
+            .map(|x| match x {
+                Ok(record) => Ok(Record::from(&record, self.columns.iter())),
+                Err(e) => {
+                    let line = e.position().map_or(0, |p| p.line());
+                    Err(AppError::InvalidRecord(line, e))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(records)
     }
 }
 
+/// Loads every `.yaml`/`.yml` parser definition in `dir`, skipping (with a
+/// warning) any file that can't be opened or parsed as a `Parser`.
+fn load_parsers_from_dir(dir: &Path) -> Vec<Parser> {
+    let mut parsers = Vec::new();
+    let entries = std::fs::read_dir(dir).expect("could not read bank directory");
+    for entry in entries {
+        let path = entry.expect("could not read directory entry").path();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => {}
+            _ => continue,
+        }
+
+        let f = match std::fs::File::open(&path) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("warning: could not open {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        match serde_yaml::from_reader(f) {
+            Ok(parser) => parsers.push(parser),
+            Err(e) => eprintln!("warning: could not parse {}: {}", path.display(), e),
+        }
+    }
+    parsers
+}
+
+/// Picks the parser whose `file_pattern` matches `file_name` the most
+/// specifically (the longest match wins). Warns when more than one
+/// candidate matches, and errors when none do.
+fn select_parser(parsers: Vec<Parser>, dir: &Path, file_name: &str) -> Result<Parser, AppError> {
+    let mut candidates: Vec<(Parser, usize)> = parsers
+        .into_iter()
+        .filter_map(|parser| {
+            let m = parser.file_pattern.as_ref()?.find(file_name)?;
+            let len = m.end() - m.start();
+            Some((parser, len))
+        })
+        .collect();
+
+    if candidates.is_empty() {
+        return Err(AppError::NoMatchingParser(
+            dir.display().to_string(),
+            file_name.to_owned(),
+        ));
+    }
+
+    if candidates.len() > 1 {
+        eprintln!("warning: multiple bank parsers match {}:", file_name);
+        for (parser, len) in &candidates {
+            eprintln!("       | - {} (matched {} chars)", parser.name, len);
+        }
+    }
+
+    candidates.sort_by_key(|(_, len)| *len);
+    Ok(candidates.pop().unwrap().0)
+}
+
 fn main() -> Result<(), AppError> {
     let matches = App::new("ynap")
         .version("0.1")
@@ -150,10 +273,31 @@ fn main() -> Result<(), AppError> {
         )
         .get_matches();
 
-    let bank_file = matches.value_of("bank").unwrap();
-    let bank_file = std::fs::File::open(bank_file).expect("could not open file");
-    let bank: Parser = serde_yaml::from_reader(bank_file).expect("could not parse YAML bank file");
-    let mut results = bank.read_from_path(matches.value_of("INPUT").unwrap())?;
+    let input_path = matches.value_of("INPUT").unwrap();
+    let bank_path = Path::new(matches.value_of("bank").unwrap());
+    let is_pdf = Path::new(input_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+
+    let mut results = if is_pdf {
+        let bank_file = std::fs::File::open(bank_path).expect("could not open file");
+        let bank: PdfParser =
+            serde_yaml::from_reader(bank_file).expect("could not parse YAML bank file");
+        bank.read_from_path(input_path)?
+    } else {
+        let bank: Parser = if bank_path.is_dir() {
+            let file_name = Path::new(input_path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(input_path);
+            select_parser(load_parsers_from_dir(bank_path), bank_path, file_name)?
+        } else {
+            let bank_file = std::fs::File::open(bank_path).expect("could not open file");
+            serde_yaml::from_reader(bank_file).expect("could not parse YAML bank file")
+        };
+        bank.read_from_path(input_path)?
+    };
 
     if let Some(rules_path) = matches.value_of("rules") {
         let f = std::fs::File::open(rules_path).expect("could not open file");
@@ -0,0 +1,131 @@
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use crate::DecimalSeparator;
+
+lazy_static! {
+    // Thousands separators such as a plain space, a non-breaking space, or a
+    // thin space, as seen in European-style bank exports (e.g. "1 234,56").
+    static ref GROUPING_WHITESPACE: Regex = Regex::new(r"[\s\u{00A0}\u{2009}]").unwrap();
+    static ref TRAILING_SIGN_MARKER: Regex = Regex::new(r"(?i)\s*(CR|DR)$").unwrap();
+}
+
+/// Parses a raw amount cell from a bank export into an exact `Decimal`.
+///
+/// Handles the cruft real statements put around the number: a `currency_symbol`
+/// to strip, thousands-grouping whitespace, a leading `-` or surrounding
+/// parentheses (`(12.34)`) for negatives, and a trailing `CR`/`DR` sign
+/// marker. What's left is normalized by `sep` and parsed as a `Decimal`.
+pub fn parse(raw: &str, sep: &DecimalSeparator, currency_symbol: Option<&str>) -> Decimal {
+    let mut s = raw.trim();
+    if s.is_empty() {
+        // Banks that split a transaction's debit and credit across two
+        // columns leave the column that doesn't apply blank.
+        return Decimal::ZERO;
+    }
+    let mut negative = false;
+
+    // A parenthesized amount, e.g. "(12.34)" or "($12.34)", is negative.
+    // Unwrap it first so the currency symbol and sign checks below see
+    // the same shape as an unparenthesized amount.
+    if let Some(inner) = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        negative = true;
+        s = inner.trim();
+    }
+
+    // A leading minus may sit outside the currency symbol ("-$12.34") or
+    // between the symbol and the digits ("$-12.34"); check both sides of
+    // the symbol strip below.
+    if let Some(rest) = s.strip_prefix('-') {
+        negative = true;
+        s = rest.trim_start();
+    }
+
+    if let Some(symbol) = currency_symbol {
+        if let Some(rest) = s.strip_prefix(symbol) {
+            s = rest.trim_start();
+        } else if let Some(rest) = s.strip_suffix(symbol) {
+            s = rest.trim_end();
+        }
+        if let Some(rest) = s.strip_prefix('-') {
+            negative = true;
+            s = rest.trim_start();
+        }
+    }
+
+    if let Some(m) = TRAILING_SIGN_MARKER.find(s) {
+        // `DR` (debit) is negative, `CR` (credit) is left positive.
+        negative |= s[m.start()..m.end()].trim().eq_ignore_ascii_case("DR");
+        s = s[..m.start()].trim_end();
+    }
+
+    let s = GROUPING_WHITESPACE.replace_all(s.trim(), "");
+    let normalized = sep.simplify(&s);
+
+    let value = Decimal::from_str(&normalized).expect("amount is not a valid decimal number");
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::DecimalSeparator;
+
+    #[test]
+    fn test_parse_plain() {
+        assert_eq!(parse("12.34", &DecimalSeparator::Period, None).to_string(), "12.34");
+    }
+
+    #[test]
+    fn test_parse_empty_is_zero() {
+        assert_eq!(parse("", &DecimalSeparator::Period, None).to_string(), "0");
+        assert_eq!(parse("   ", &DecimalSeparator::Period, None).to_string(), "0");
+    }
+
+    #[test]
+    fn test_parse_leading_minus() {
+        assert_eq!(parse("-12.34", &DecimalSeparator::Period, None).to_string(), "-12.34");
+    }
+
+    #[test]
+    fn test_parse_parenthesized_negative() {
+        assert_eq!(parse("(12.34)", &DecimalSeparator::Period, None).to_string(), "-12.34");
+    }
+
+    #[test]
+    fn test_parse_trailing_sign_marker() {
+        assert_eq!(parse("12.34 DR", &DecimalSeparator::Period, None).to_string(), "-12.34");
+        assert_eq!(parse("12.34 CR", &DecimalSeparator::Period, None).to_string(), "12.34");
+    }
+
+    #[test]
+    fn test_parse_currency_symbol() {
+        assert_eq!(parse("$12.34", &DecimalSeparator::Period, Some("$")).to_string(), "12.34");
+    }
+
+    #[test]
+    fn test_parse_negative_currency_symbol() {
+        assert_eq!(parse("-$12.34", &DecimalSeparator::Period, Some("$")).to_string(), "-12.34");
+        assert_eq!(parse("$-12.34", &DecimalSeparator::Period, Some("$")).to_string(), "-12.34");
+    }
+
+    #[test]
+    fn test_parse_parenthesized_negative_with_currency_symbol() {
+        assert_eq!(parse("($12.34)", &DecimalSeparator::Period, Some("$")).to_string(), "-12.34");
+    }
+
+    #[test]
+    fn test_parse_grouping_whitespace_and_comma_separator() {
+        assert_eq!(
+            parse("1 234,56", &DecimalSeparator::Comma, None).to_string(),
+            "1234.56"
+        );
+    }
+}
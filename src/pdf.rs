@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use lopdf::content::Content;
+use lopdf::{Document, Object};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_regex;
+
+use ynap::{Field, Record};
+
+use crate::AppError;
+
+/// How close two fragments' y-coordinates may be while still counting as
+/// the same row. Bank statement PDFs aren't typeset with perfect precision,
+/// so a small error margin absorbs sub-pixel baseline jitter.
+const DEFAULT_ROW_TOLERANCE: f64 = 2.0;
+
+fn default_row_tolerance() -> f64 {
+    DEFAULT_ROW_TOLERANCE
+}
+
+/// One column of a PDF statement table: the header label printed on the
+/// page, and the `Field` its cells should be routed into.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PdfColumn {
+    pub header: String,
+    #[serde(flatten)]
+    pub field: Field,
+}
+
+/// Parses tabular PDF bank statements into `Record`s, the PDF counterpart
+/// to the CSV-oriented `Parser`.
+///
+/// Since PDFs carry no structural notion of rows and columns, the header
+/// row is located by matching `columns[].header` against the page text,
+/// and every fragment below it is bucketed into a row by y-coordinate and
+/// a column by x-coordinate.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PdfParser {
+    pub name: String,
+    #[serde(with = "serde_regex")]
+    pub file_pattern: Option<Regex>,
+    pub columns: Vec<PdfColumn>,
+    #[serde(default = "default_row_tolerance")]
+    pub row_tolerance: f64,
+}
+
+/// A single piece of text on the page together with the position of its
+/// baseline origin.
+#[derive(Debug, Clone)]
+struct Fragment {
+    x: f64,
+    y: f64,
+    text: String,
+}
+
+impl PdfParser {
+    pub fn read_from_path(&self, path: impl AsRef<Path>) -> Result<Vec<Record>, AppError> {
+        let doc = Document::load(path).map_err(AppError::Pdf)?;
+
+        let mut records = Vec::new();
+        for page_id in doc.page_iter() {
+            let fragments = extract_fragments(&doc, page_id)?;
+            records.extend(self.records_from_page(&fragments));
+        }
+        Ok(records)
+    }
+
+    /// Finds the header row on a page (if any) and turns every row below
+    /// it into a `Record`. Pages without a recognizable header (e.g. a
+    /// cover page) simply yield no records.
+    fn records_from_page(&self, fragments: &[Fragment]) -> Vec<Record> {
+        let Some((header_y, column_x)) = self.locate_header(fragments) else {
+            return Vec::new();
+        };
+
+        // Group every fragment strictly below the header row into rows by
+        // clustering y-coordinates within `row_tolerance`.
+        let mut body: Vec<&Fragment> = fragments
+            .iter()
+            .filter(|f| f.y < header_y - self.row_tolerance)
+            .collect();
+        body.sort_by(|a, b| b.y.partial_cmp(&a.y).unwrap());
+
+        let mut rows: Vec<Vec<&Fragment>> = Vec::new();
+        for frag in body {
+            match rows.last_mut() {
+                Some(row) if (row[0].y - frag.y).abs() <= self.row_tolerance => row.push(frag),
+                _ => rows.push(vec![frag]),
+            }
+        }
+
+        // A wrapped memo (or other multi-line cell) sits a full line below
+        // its transaction's primary row, well past `row_tolerance`, and so
+        // becomes its own row above. A new transaction always starts with
+        // a date, so any row with nothing in the date column is a
+        // continuation of the previous row rather than a transaction of
+        // its own; fold it in instead of emitting a spurious `Record`.
+        let date_column = self.columns.iter().position(|c| matches!(c.field, Field::Date(_)));
+        let mut merged_rows: Vec<Vec<&Fragment>> = Vec::new();
+        for row in rows {
+            let is_continuation = date_column.is_some_and(|date_col| {
+                !merged_rows.is_empty() && !row_has_column(&row, date_col, &column_x)
+            });
+            if is_continuation {
+                merged_rows.last_mut().unwrap().extend(row);
+            } else {
+                merged_rows.push(row);
+            }
+        }
+
+        merged_rows
+            .iter()
+            .map(|row| self.record_from_row(row, &column_x))
+            .collect()
+    }
+
+    /// Finds the header labels among `fragments` and returns the row's
+    /// y-coordinate plus each configured column's x-coordinate.
+    fn locate_header(&self, fragments: &[Fragment]) -> Option<(f64, HashMap<usize, f64>)> {
+        let mut column_x = HashMap::new();
+        let mut header_y = None;
+
+        for (i, column) in self.columns.iter().enumerate() {
+            if let Some(frag) = fragments.iter().find(|f| f.text.trim() == column.header) {
+                column_x.insert(i, frag.x);
+                header_y.get_or_insert(frag.y);
+            }
+        }
+
+        header_y.map(|y| (y, column_x))
+    }
+
+    /// Assigns each fragment in a row to its nearest column by x-distance,
+    /// concatenating multi-line cells (e.g. a wrapped memo) that land in
+    /// the same row and column, then maps the row onto a `Record`.
+    fn record_from_row(&self, row: &[&Fragment], column_x: &HashMap<usize, f64>) -> Record {
+        let mut cells: HashMap<usize, String> = HashMap::new();
+        for frag in row {
+            if let Some(i) = nearest_column(frag.x, column_x) {
+                let cell = cells.entry(i).or_default();
+                if !cell.is_empty() {
+                    cell.push(' ');
+                }
+                cell.push_str(frag.text.trim());
+            }
+        }
+
+        let mut record = Record::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            let v = cells.remove(&i).unwrap_or_default();
+            record.apply_field(&column.field, v);
+        }
+        record
+    }
+}
+
+/// Finds the column whose x-position is closest to `x`.
+fn nearest_column(x: f64, column_x: &HashMap<usize, f64>) -> Option<usize> {
+    column_x
+        .iter()
+        .min_by(|(_, ax), (_, bx)| (x - **ax).abs().partial_cmp(&(x - **bx).abs()).unwrap())
+        .map(|(i, _)| *i)
+}
+
+/// Whether any fragment in `row` is nearest to `column`.
+fn row_has_column(row: &[&Fragment], column: usize, column_x: &HashMap<usize, f64>) -> bool {
+    row.iter().any(|f| nearest_column(f.x, column_x) == Some(column))
+}
+
+/// Walks a page's content stream collecting the fragment of every `Tj` and
+/// `TJ` operator along with the x/y coordinates of its text matrix at the
+/// time it was drawn, as set by `BT`, `Td`/`TD` and `Tm`. Text shown with
+/// the `'` and `"` operators is not collected, and `T*` line moves are not
+/// tracked.
+fn extract_fragments(doc: &Document, page_id: (u32, u16)) -> Result<Vec<Fragment>, AppError> {
+    let content_data = doc.get_page_content(page_id).map_err(AppError::Pdf)?;
+    let content = Content::decode(&content_data).map_err(AppError::Pdf)?;
+
+    let mut fragments = Vec::new();
+    let mut tx = 0.0;
+    let mut ty = 0.0;
+
+    for op in content.operations {
+        match op.operator.as_str() {
+            // Each text object starts over from the identity text matrix.
+            "BT" => {
+                tx = 0.0;
+                ty = 0.0;
+            }
+            "Td" | "TD" => {
+                if let (Some(dx), Some(dy)) = (op.operands.get(0), op.operands.get(1)) {
+                    tx += as_f64(dx);
+                    ty += as_f64(dy);
+                }
+            }
+            "Tm" => {
+                if let (Some(e), Some(f)) = (op.operands.get(4), op.operands.get(5)) {
+                    tx = as_f64(e);
+                    ty = as_f64(f);
+                }
+            }
+            "Tj" => {
+                if let Some(Object::String(bytes, _)) = op.operands.get(0) {
+                    fragments.push(Fragment {
+                        x: tx,
+                        y: ty,
+                        text: decode_text(bytes),
+                    });
+                }
+            }
+            "TJ" => {
+                if let Some(Object::Array(items)) = op.operands.get(0) {
+                    let mut text = String::new();
+                    for item in items {
+                        if let Object::String(bytes, _) = item {
+                            text.push_str(&decode_text(bytes));
+                        }
+                    }
+                    if !text.is_empty() {
+                        fragments.push(Fragment { x: tx, y: ty, text });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(fragments)
+}
+
+fn as_f64(obj: &Object) -> f64 {
+    obj.as_float()
+        .map(|v| v as f64)
+        .or_else(|_| obj.as_i64().map(|v| v as f64))
+        .unwrap_or(0.0)
+}
+
+fn decode_text(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fragment, PdfColumn, PdfParser};
+    use ynap::Field;
+
+    fn frag(x: f64, y: f64, text: &str) -> Fragment {
+        Fragment { x, y, text: text.to_owned() }
+    }
+
+    fn parser() -> PdfParser {
+        PdfParser {
+            name: "test".to_owned(),
+            file_pattern: None,
+            columns: vec![
+                PdfColumn { header: "Date".to_owned(), field: Field::Date(String::new()) },
+                PdfColumn { header: "Payee".to_owned(), field: Field::Payee },
+                PdfColumn { header: "Memo".to_owned(), field: Field::Memo },
+            ],
+            row_tolerance: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_locate_header() {
+        let fragments = vec![
+            frag(10.0, 700.0, "Date"),
+            frag(100.0, 700.0, "Payee"),
+            frag(200.0, 700.0, "Memo"),
+            frag(10.0, 680.0, "2024-01-02"),
+        ];
+        let (header_y, column_x) = parser().locate_header(&fragments).unwrap();
+        assert_eq!(header_y, 700.0);
+        assert_eq!(column_x.get(&0), Some(&10.0));
+        assert_eq!(column_x.get(&1), Some(&100.0));
+        assert_eq!(column_x.get(&2), Some(&200.0));
+    }
+
+    #[test]
+    fn test_locate_header_missing_is_none() {
+        let fragments = vec![frag(10.0, 680.0, "2024-01-02")];
+        assert!(parser().locate_header(&fragments).is_none());
+    }
+
+    #[test]
+    fn test_record_from_row() {
+        let p = parser();
+        let row = vec![
+            frag(10.0, 680.0, "2024-01-02"),
+            frag(100.0, 680.0, "Acme Corp"),
+            frag(200.0, 680.0, "Coffee"),
+        ];
+        let row_refs: Vec<&Fragment> = row.iter().collect();
+        let mut column_x = std::collections::HashMap::new();
+        column_x.insert(0, 10.0);
+        column_x.insert(1, 100.0);
+        column_x.insert(2, 200.0);
+
+        let record = p.record_from_row(&row_refs, &column_x);
+        assert_eq!(record.date, "2024-01-02");
+        assert_eq!(record.payee, "Acme Corp");
+        assert_eq!(record.memo, "Coffee");
+    }
+
+    #[test]
+    fn test_records_from_page_merges_wrapped_memo() {
+        let p = parser();
+        let fragments = vec![
+            frag(10.0, 700.0, "Date"),
+            frag(100.0, 700.0, "Payee"),
+            frag(200.0, 700.0, "Memo"),
+            // Primary row of the transaction.
+            frag(10.0, 680.0, "2024-01-02"),
+            frag(100.0, 680.0, "Acme Corp"),
+            frag(200.0, 680.0, "First line of memo"),
+            // Wrapped continuation of the memo cell, a full text line
+            // (12 units) below the primary row -- well past `row_tolerance`.
+            frag(200.0, 668.0, "second line"),
+        ];
+
+        let records = p.records_from_page(&fragments);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].date, "2024-01-02");
+        assert_eq!(records[0].payee, "Acme Corp");
+        assert_eq!(records[0].memo, "First line of memo second line");
+    }
+
+    #[test]
+    fn test_records_from_page_distinct_transactions_stay_separate() {
+        let p = parser();
+        let fragments = vec![
+            frag(10.0, 700.0, "Date"),
+            frag(100.0, 700.0, "Payee"),
+            frag(200.0, 700.0, "Memo"),
+            frag(10.0, 680.0, "2024-01-02"),
+            frag(100.0, 680.0, "Acme Corp"),
+            frag(10.0, 668.0, "2024-01-03"),
+            frag(100.0, 668.0, "Other Corp"),
+        ];
+
+        let records = p.records_from_page(&fragments);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].payee, "Acme Corp");
+        assert_eq!(records[1].payee, "Other Corp");
+    }
+}
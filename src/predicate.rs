@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_regex;
+
+use crate::Record;
+
+/// An expression tree for matching a `Record`, going beyond a flat AND of
+/// per-field regexes: numeric and date comparisons, set membership, ranges,
+/// and boolean combinators.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "args")]
+pub enum Predicate {
+    #[serde(rename = "and")]
+    And(Vec<Predicate>),
+    #[serde(rename = "or")]
+    Or(Vec<Predicate>),
+    #[serde(rename = "not")]
+    Not(Box<Predicate>),
+
+    #[serde(rename = "~")]
+    Match {
+        field: String,
+        #[serde(with = "serde_regex")]
+        pattern: Regex,
+    },
+    #[serde(rename = "=")]
+    Eq { field: String, value: String },
+    #[serde(rename = "!=")]
+    Ne { field: String, value: String },
+
+    #[serde(rename = "<")]
+    Lt { field: String, value: Decimal },
+    #[serde(rename = "<=")]
+    Le { field: String, value: Decimal },
+    #[serde(rename = ">")]
+    Gt { field: String, value: Decimal },
+    #[serde(rename = ">=")]
+    Ge { field: String, value: Decimal },
+
+    #[serde(rename = "in")]
+    In { field: String, values: Vec<String> },
+    #[serde(rename = "between")]
+    Between {
+        field: String,
+        low: String,
+        high: String,
+    },
+}
+
+/// A field value coerced for ordered comparison: numbers and dates compare
+/// by value, everything else falls back to a lexicographic string compare.
+#[derive(Debug, PartialEq, PartialOrd)]
+enum Comparable {
+    Number(Decimal),
+    Date(NaiveDate),
+    Text(String),
+}
+
+fn comparable(s: &str) -> Comparable {
+    if let Ok(d) = Decimal::from_str(s.trim()) {
+        Comparable::Number(d)
+    } else if let Ok(date) = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d") {
+        Comparable::Date(date)
+    } else {
+        Comparable::Text(s.to_owned())
+    }
+}
+
+fn field_value(record: &Record, field: &str) -> Option<String> {
+    record.get(field).map(|v| v.into_owned())
+}
+
+fn numeric(record: &Record, field: &str) -> Option<Decimal> {
+    field_value(record, field).and_then(|v| Decimal::from_str(v.trim()).ok())
+}
+
+impl Predicate {
+    /// Evaluates this predicate against `record`, recording any named
+    /// captures from `~` leaves into `captures` so they remain available to
+    /// `template::interpolate` in the transformer's `replace` block.
+    pub fn eval(&self, record: &Record, captures: &mut HashMap<String, String>) -> bool {
+        match self {
+            Predicate::And(nodes) => nodes.iter().all(|p| p.eval(record, captures)),
+            Predicate::Or(nodes) => nodes.iter().any(|p| p.eval(record, captures)),
+            Predicate::Not(node) => !node.eval(record, captures),
+
+            Predicate::Match { field, pattern } => match record.get(field) {
+                Some(value) => match pattern.captures(&value) {
+                    Some(rc) => {
+                        for n in pattern.capture_names().flatten() {
+                            if let Some(g) = rc.name(n) {
+                                captures.insert(n.into(), g.as_str().to_owned());
+                            }
+                        }
+                        true
+                    }
+                    None => false,
+                },
+                None => false,
+            },
+            Predicate::Eq { field, value } => field_value(record, field).as_deref() == Some(value.as_str()),
+            Predicate::Ne { field, value } => field_value(record, field).as_deref() != Some(value.as_str()),
+
+            Predicate::Lt { field, value } => numeric(record, field).is_some_and(|v| v < *value),
+            Predicate::Le { field, value } => numeric(record, field).is_some_and(|v| v <= *value),
+            Predicate::Gt { field, value } => numeric(record, field).is_some_and(|v| v > *value),
+            Predicate::Ge { field, value } => numeric(record, field).is_some_and(|v| v >= *value),
+
+            Predicate::In { field, values } => match field_value(record, field) {
+                Some(v) => values.iter().any(|x| x == &v),
+                None => false,
+            },
+            Predicate::Between { field, low, high } => match field_value(record, field) {
+                Some(v) => {
+                    let v = comparable(&v);
+                    comparable(low) <= v && v <= comparable(high)
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Predicate;
+    use crate::Record;
+    use std::collections::HashMap;
+
+    fn record() -> Record {
+        let mut r = Record::new();
+        r.date = "2024-03-15".to_owned();
+        r.payee = "Acme Corp".to_owned();
+        r.amount = "150.00".parse().unwrap();
+        r
+    }
+
+    fn eval(p: &Predicate, r: &Record) -> bool {
+        p.eval(r, &mut HashMap::new())
+    }
+
+    #[test]
+    fn test_eq_ne() {
+        let r = record();
+        assert!(eval(&Predicate::Eq { field: "payee".into(), value: "Acme Corp".into() }, &r));
+        assert!(eval(&Predicate::Ne { field: "payee".into(), value: "Other".into() }, &r));
+    }
+
+    #[test]
+    fn test_numeric_comparisons() {
+        let r = record();
+        assert!(eval(&Predicate::Gt { field: "amount".into(), value: "100".parse().unwrap() }, &r));
+        assert!(eval(&Predicate::Le { field: "amount".into(), value: "150.00".parse().unwrap() }, &r));
+        assert!(!eval(&Predicate::Lt { field: "amount".into(), value: "100".parse().unwrap() }, &r));
+    }
+
+    #[test]
+    fn test_numeric_comparison_missing_field_is_false() {
+        let r = record();
+        assert!(!eval(&Predicate::Gt { field: "memo".into(), value: "0".parse().unwrap() }, &r));
+    }
+
+    #[test]
+    fn test_in_and_between() {
+        let r = record();
+        assert!(eval(
+            &Predicate::In { field: "payee".into(), values: vec!["Acme Corp".into(), "Other".into()] },
+            &r
+        ));
+        assert!(eval(
+            &Predicate::Between { field: "date".into(), low: "2024-01-01".into(), high: "2024-12-31".into() },
+            &r
+        ));
+    }
+
+    #[test]
+    fn test_and_or_not() {
+        let r = record();
+        let gt = Predicate::Gt { field: "amount".into(), value: "100".parse().unwrap() };
+        let lt = Predicate::Lt { field: "amount".into(), value: "100".parse().unwrap() };
+        assert!(eval(&Predicate::And(vec![gt, Predicate::Not(Box::new(lt))]), &r));
+
+        let a = Predicate::Eq { field: "payee".into(), value: "nope".into() };
+        let b = Predicate::Eq { field: "payee".into(), value: "Acme Corp".into() };
+        assert!(eval(&Predicate::Or(vec![a, b]), &r));
+    }
+
+    #[test]
+    fn test_match_captures() {
+        let r = record();
+        let p = Predicate::Match {
+            field: "payee".into(),
+            pattern: regex::Regex::new(r"^(?P<first>\w+) Corp$").unwrap(),
+        };
+        let mut captures = HashMap::new();
+        assert!(p.eval(&r, &mut captures));
+        assert_eq!(captures.get("first").map(|s| s.as_str()), Some("Acme"));
+    }
+}
@@ -1,15 +1,117 @@
+use std::str::FromStr;
+
+use chrono::NaiveDate;
 use inflector::Inflector;
 use lazy_static::lazy_static;
 use regex::Regex;
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("invalid template command `{command}` for key `{key}`")]
+    InvalidCommand { key: String, command: String },
+
+    #[error("value for key `{key}` must not be empty")]
+    EmptyValue { key: String },
+
+    #[error("value for key `{key}` is not a valid date: {source}")]
+    InvalidDate {
+        key: String,
+        #[source]
+        source: chrono::ParseError,
+    },
+
+    #[error("value for key `{key}` is not a valid amount: {source}")]
+    InvalidAmount {
+        key: String,
+        #[source]
+        source: rust_decimal::Error,
+    },
+}
+
+/// A single `|`-separated template command, e.g. `lowercase` or `date:%d/%m/%Y`.
+struct Command<'a> {
+    verb: &'a str,
+    arg: Option<&'a str>,
+}
+
+fn apply(key: &str, value: String, command: &Command) -> Result<String, TemplateError> {
+    Ok(match (command.verb, command.arg) {
+        ("title_case", _) => value.to_title_case(),
+        ("lowercase", _) => value.to_lowercase(),
+        ("uppercase", _) => value.to_uppercase(),
+        ("trim", _) => value.trim().to_owned(),
+        ("not_empty", _) => {
+            if value.is_empty() {
+                return Err(TemplateError::EmptyValue { key: key.to_owned() });
+            }
+            value
+        }
+        ("default", Some(default)) => {
+            if value.is_empty() {
+                default.to_owned()
+            } else {
+                value
+            }
+        }
+        ("truncate", Some(n)) => {
+            let n: usize = n.parse().map_err(|_| TemplateError::InvalidCommand {
+                key: key.to_owned(),
+                command: format!("truncate:{}", n),
+            })?;
+            value.chars().take(n).collect()
+        }
+        ("date", Some(fmt)) => {
+            let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|source| {
+                TemplateError::InvalidDate {
+                    key: key.to_owned(),
+                    source,
+                }
+            })?;
+            date.format(fmt).to_string()
+        }
+        ("abs", _) => format_amount(key, &value, Decimal::abs)?,
+        ("negate", _) => format_amount(key, &value, |d| -d)?,
+        (verb, arg) => {
+            let command = match arg {
+                Some(arg) => format!("{}:{}", verb, arg),
+                None => verb.to_owned(),
+            };
+            return Err(TemplateError::InvalidCommand {
+                key: key.to_owned(),
+                command,
+            });
+        }
+    })
+}
+
+fn format_amount(
+    key: &str,
+    value: &str,
+    op: impl Fn(&Decimal) -> Decimal,
+) -> Result<String, TemplateError> {
+    let amount = Decimal::from_str(value).map_err(|source| TemplateError::InvalidAmount {
+        key: key.to_owned(),
+        source,
+    })?;
+    Ok(format!("{:.2}", op(&amount)))
+}
 
-pub fn interpolate(tmpl: &str, func: impl Fn(&str) -> String) -> String {
+/// Expands `${key}` placeholders in `tmpl`, looking up each key with `func`
+/// and piping the result left to right through any `|`-separated commands,
+/// e.g. `${payee|lowercase|title_case}`.
+pub fn interpolate(
+    tmpl: &str,
+    func: impl Fn(&str) -> String,
+) -> Result<String, TemplateError> {
     lazy_static! {
         static ref PLACEHOLDER: Regex =
-            Regex::new(r"(?mi)\$\{([[:word:]]+)(\|([[:word:]]+))?\}").unwrap();
+            Regex::new(r"(?mi)\$\{([[:word:]]+)((?:\|[^{}|]+)*)\}").unwrap();
     }
 
     if !PLACEHOLDER.is_match(tmpl) {
-        return tmpl.to_owned();
+        return Ok(tmpl.to_owned());
     }
 
     let mut buffer: Vec<String> = Vec::new();
@@ -22,20 +124,20 @@ pub fn interpolate(tmpl: &str, func: impl Fn(&str) -> String) -> String {
         let key = placeholder.get(1).unwrap().as_str(); // This group not optional.
         let mut value = func(key);
 
-        if let Some(command) = placeholder.get(3) {
-            value = match command.as_str() {
-                "title_case" => value.to_title_case(),
-                "lowercase" => value.to_lowercase(),
-                "uppercase" => value.to_uppercase(),
-                "not_empty" => {
-                    if value.is_empty() {
-                        panic!("value of key {} cannot be empty", key);
-                    } else {
-                        value
-                    }
-                }
-                invalid => panic!("invalid command: {}", invalid),
-            };
+        if let Some(pipeline) = placeholder.get(2) {
+            for segment in pipeline.as_str().split('|').filter(|s| !s.is_empty()) {
+                let command = match segment.split_once(':') {
+                    Some((verb, arg)) => Command {
+                        verb,
+                        arg: Some(arg),
+                    },
+                    None => Command {
+                        verb: segment,
+                        arg: None,
+                    },
+                };
+                value = apply(key, value, &command)?;
+            }
         }
 
         buffer.push(value);
@@ -45,7 +147,7 @@ pub fn interpolate(tmpl: &str, func: impl Fn(&str) -> String) -> String {
         buffer.push(tmpl[index..].into());
     }
 
-    buffer.join("")
+    Ok(buffer.join(""))
 }
 
 #[cfg(test)]
@@ -61,10 +163,17 @@ mod tests {
             ("${a} ${b}!", "hello world!"),
             ("${a|title_case} ${b}!", "Hello world!"),
             ("${a|not_empty} ${b|uppercase}!", "hello WORLD!"),
+            ("${a|uppercase|lowercase}", "hello"),
         ];
 
         for test in tests {
-            assert_eq!(interpolate(test.0, func), test.1.to_string());
+            assert_eq!(interpolate(test.0, func).unwrap(), test.1.to_string());
         }
     }
+
+    #[test]
+    fn test_interpolate_invalid_command() {
+        let func = |_: &str| "value".to_string();
+        assert!(interpolate("${a|not_a_real_command}", func).is_err());
+    }
 }